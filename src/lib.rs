@@ -6,32 +6,138 @@
 //
 //        http://www.apache.org/licenses/LICENSE-2.0
 
+use std::collections::HashMap;
 use std::future::{Ready, ready};
+use std::rc::Rc;
 
-use actix_web::{dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform}, Error, http::header, HttpResponseBuilder};
-use actix_web::body::{EitherBody};
+use actix_web::{dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform}, error::InternalError, Error, http::{header, header::{HeaderName, HeaderValue}, StatusCode}, HttpResponse, HttpResponseBuilder};
+use actix_web::body::{to_bytes, BoxBody, EitherBody, MessageBody};
 use futures_util::future::LocalBoxFuture;
 use futures_util::FutureExt;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 #[derive(Serialize, Deserialize)]
 pub struct JsonErrorMessage {
     /// A JSON Serializable Struct for an Error Response
     pub error: u16,
     pub message: String,
+    /// The original response body, when the downstream handler already
+    /// returned one and it wasn't already `application/json`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
 }
 
-pub struct JsonErrorMiddlewareDefinition<S> {
+/// The JSON envelope a [`JsonMiddleware`] synthesizes for error responses.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorFormat {
+    /// The crate's original `{error, message}` body.
+    #[default]
+    Simple,
+    /// An [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807) `application/problem+json` body.
+    ProblemJson,
+}
+
+/// A JSON Serializable Struct for an RFC 7807 `application/problem+json` Error Response
+#[derive(Serialize, Deserialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub type_uri: String,
+    pub title: String,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub instance: String,
+}
+
+/// Returns true if `headers` already declare an `application/json` body,
+/// in which case the middleware should leave it untouched.
+fn is_json_content_type(headers: &header::HeaderMap) -> bool {
+    headers.get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| {
+            v.split(';').next().unwrap_or("").trim().eq_ignore_ascii_case("application/json")
+        })
+        .unwrap_or(false)
+}
+
+/// A per-status-code error handler, modeled on actix-web's `ErrorHandlers`.
+///
+/// Receives the outgoing `ServiceResponse<B>` and must return a
+/// `ServiceResponse<EitherBody<B>>`, so a handler can either replace the
+/// body with its own JSON or pass the original response through untouched.
+type ErrorHandlerFn<B> = dyn Fn(ServiceResponse<B>) -> ServiceResponse<EitherBody<B>>;
+
+/// An observability hook invoked whenever the middleware observes a >299
+/// response or a downstream `Err`, including responses it then passes
+/// through unchanged.
+///
+/// Takes the request path rather than the `HttpRequest` itself: actix-web
+/// requires exclusive ownership of a request's internals while it is being
+/// routed, so holding a cloned `HttpRequest` across the inner service call
+/// would panic on every request, not just failing ones.
+type OnErrorFn = dyn Fn(StatusCode, &str);
+
+/// Builds the `HttpResponse<BoxBody>` for a status code the middleware is
+/// handling generically (no per-status or default handler matched),
+/// honoring the configured [`ErrorFormat`] and schema customization.
+#[allow(clippy::too_many_arguments)]
+fn build_error_body(
+    status_code: StatusCode,
+    detail: Option<String>,
+    instance: String,
+    format: ErrorFormat,
+    error_field: &str,
+    message_field: &str,
+    extra_fields: &Map<String, Value>,
+) -> HttpResponse<BoxBody> {
+    match format {
+        ErrorFormat::Simple => {
+            let mut fields = Map::new();
+            fields.insert(error_field.to_string(), Value::from(status_code.as_u16()));
+            fields.insert(message_field.to_string(), Value::from(status_code.to_string()));
+            if let Some(detail) = detail {
+                fields.insert("detail".to_string(), Value::from(detail));
+            }
+            for (key, value) in extra_fields.iter() {
+                fields.insert(key.clone(), value.clone());
+            }
+            HttpResponseBuilder::new(status_code).json(Value::Object(fields))
+        }
+        ErrorFormat::ProblemJson => {
+            let mut builder = HttpResponseBuilder::new(status_code);
+            builder.insert_header((header::CONTENT_TYPE, "application/problem+json"));
+            builder.json(ProblemDetails {
+                type_uri: "about:blank".to_string(),
+                title: status_code.canonical_reason().unwrap_or("").to_string(),
+                status: status_code.as_u16(),
+                detail,
+                instance,
+            })
+        }
+    }
+}
+
+pub struct JsonErrorMiddlewareDefinition<S, B> {
     /// A Middleware Definition Struct for The Service Component of the Middleware
     service: S,
+    handlers: HashMap<StatusCode, Rc<ErrorHandlerFn<B>>>,
+    default_handler: Option<Rc<ErrorHandlerFn<B>>>,
+    format: ErrorFormat,
+    error_field: String,
+    message_field: String,
+    extra_fields: Map<String, Value>,
+    extra_headers: Vec<(HeaderName, HeaderValue)>,
+    on_error: Option<Rc<OnErrorFn>>,
+    force_json_content_type: bool,
 }
 
 
-impl<S, B> Service<ServiceRequest> for JsonErrorMiddlewareDefinition<S>
+impl<S, B> Service<ServiceRequest> for JsonErrorMiddlewareDefinition<S, B>
     where
         S: Service<ServiceRequest, Response=ServiceResponse<B>, Error=Error>,
         S::Future: 'static,
-        B: 'static,
+        B: MessageBody + 'static,
 {
     type Response = ServiceResponse<EitherBody<B>>;
     type Error = Error;
@@ -40,54 +146,237 @@ impl<S, B> Service<ServiceRequest> for JsonErrorMiddlewareDefinition<S>
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
+        let path = req.path().to_owned();
         let fut = self.service.call(req);
+        let handlers = self.handlers.clone();
+        let default_handler = self.default_handler.clone();
+        let format = self.format;
+        let error_field = self.error_field.clone();
+        let message_field = self.message_field.clone();
+        let extra_fields = self.extra_fields.clone();
+        let extra_headers = self.extra_headers.clone();
+        let on_error = self.on_error.clone();
+        let force_json_content_type = self.force_json_content_type;
 
         async move {
             let res_result: Result<ServiceResponse<B>, Error> = fut.await;
-            let mut res = res_result.ok().expect("response found");
+
+            let res = match res_result {
+                Ok(res) => res,
+                Err(err) => {
+                    let status_code = err.as_response_error().status_code();
+                    if let Some(on_error) = &on_error {
+                        on_error(status_code, &path);
+                    }
+
+                    // The `ServiceRequest` was moved into the inner service
+                    // call, and cloning its `HttpRequest` beforehand to build
+                    // a `ServiceResponse` here
+                    // would hold an extra `Rc` reference alive across the
+                    // inner `self.service.call`, which actix-web's router
+                    // relies on being uniquely owned. Pairing the original
+                    // error with our JSON body via `InternalError` lets
+                    // actix-web render it without needing a request at all.
+                    let mut body = build_error_body(
+                        status_code, None, path, format, &error_field, &message_field, &extra_fields,
+                    );
+                    apply_extra_headers(body.headers_mut(), &extra_headers);
+                    return Err(InternalError::from_response(err, body).into());
+                }
+            };
 
             let status_code = res.status();
             if status_code.as_u16() > 299 {
-                let response = HttpResponseBuilder::new(status_code).json(
-                    JsonErrorMessage {
-                        error: status_code.as_u16(),
-                        message: status_code.to_string(),
-                    }
+                if let Some(on_error) = &on_error {
+                    on_error(status_code, res.request().path());
+                }
+
+                if let Some(handler) = handlers.get(&status_code).or(default_handler.as_ref()) {
+                    let mut res = handler(res);
+                    apply_extra_headers(res.headers_mut(), &extra_headers);
+                    return Ok(res);
+                }
+
+                if is_json_content_type(res.headers()) {
+                    let mut res = res.map_into_left_body();
+                    apply_extra_headers(res.headers_mut(), &extra_headers);
+                    return Ok(res);
+                }
+
+                let instance = res.request().path().to_string();
+                let (req, http_res) = res.into_parts();
+                let body = to_bytes(http_res.into_body()).await.unwrap_or_default();
+                let detail = if body.is_empty() {
+                    None
+                } else {
+                    std::str::from_utf8(&body).ok().map(str::to_owned)
+                };
+
+                let response = build_error_body(
+                    status_code, detail, instance, format, &error_field, &message_field, &extra_fields,
                 ).map_into_right_body();
-                return Ok(ServiceResponse::into_response(res, response));
+                let mut res = ServiceResponse::new(req, response);
+                apply_extra_headers(res.headers_mut(), &extra_headers);
+                Ok(res)
             } else {
-                res.headers_mut().insert(
-                    header::CONTENT_TYPE,
-                    header::HeaderValue::from_static("application/json"));
+                let mut res = res;
+                if force_json_content_type || !res.headers().contains_key(header::CONTENT_TYPE) {
+                    res.headers_mut().insert(
+                        header::CONTENT_TYPE,
+                        header::HeaderValue::from_static("application/json"));
+                }
                 Ok(res.map_into_left_body())
             }
         }.boxed_local()
     }
 }
 
+/// Inserts each configured header into `headers`, overwriting any existing value.
+fn apply_extra_headers(headers: &mut header::HeaderMap, extra_headers: &[(HeaderName, HeaderValue)]) {
+    for (name, value) in extra_headers {
+        headers.insert(name.clone(), value.clone());
+    }
+}
 
-pub struct JsonMiddleware;
 
-impl JsonMiddleware {
+pub struct JsonMiddleware<B = actix_web::body::BoxBody> {
+    handlers: HashMap<StatusCode, Rc<ErrorHandlerFn<B>>>,
+    default_handler: Option<Rc<ErrorHandlerFn<B>>>,
+    format: ErrorFormat,
+    error_field: String,
+    message_field: String,
+    extra_fields: Map<String, Value>,
+    extra_headers: Vec<(HeaderName, HeaderValue)>,
+    on_error: Option<Rc<OnErrorFn>>,
+    force_json_content_type: bool,
+}
+
+impl<B> Default for JsonMiddleware<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B> JsonMiddleware<B> {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            handlers: HashMap::new(),
+            default_handler: None,
+            format: ErrorFormat::Simple,
+            error_field: "error".to_string(),
+            message_field: "message".to_string(),
+            extra_fields: Map::new(),
+            extra_headers: Vec::new(),
+            on_error: None,
+            force_json_content_type: false,
+        }
+    }
+
+    /// Selects the JSON envelope used for the generic, non-handled error body.
+    pub fn format(mut self, format: ErrorFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Renames the `error` field of the `ErrorFormat::Simple` body, e.g. to `code`.
+    pub fn error_field(mut self, name: impl Into<String>) -> Self {
+        self.error_field = name.into();
+        self
+    }
+
+    /// Renames the `message` field of the `ErrorFormat::Simple` body, e.g. to `reason`.
+    pub fn message_field(mut self, name: impl Into<String>) -> Self {
+        self.message_field = name.into();
+        self
+    }
+
+    /// Adds a static field to the `ErrorFormat::Simple` body, e.g. `{"service": "..."}`.
+    pub fn extra_field(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.extra_fields.insert(name.into(), value.into());
+        self
+    }
+
+    /// Adds a header inserted into every error response produced by this middleware.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.extra_headers.push((name, value));
+        self
+    }
+
+    /// Registers a handler that builds the response for a specific status code,
+    /// overriding the generic `{error, message}` body for that code only.
+    ///
+    /// Only applies to responses the downstream service actually returned
+    /// (the `Ok` path); a downstream `Err` is always rendered with the
+    /// generic body, since there's no `ServiceResponse` to hand the handler.
+    pub fn handler<F>(mut self, status_code: StatusCode, handler: F) -> Self
+        where
+            F: Fn(ServiceResponse<B>) -> ServiceResponse<EitherBody<B>> + 'static,
+    {
+        self.handlers.insert(status_code, Rc::new(handler));
+        self
+    }
+
+    /// Registers a fallback handler used for any status code > 299 that has
+    /// no specific handler registered via [`JsonMiddleware::handler`].
+    ///
+    /// Like [`JsonMiddleware::handler`], this only applies to the `Ok`
+    /// path; a downstream `Err` always gets the generic body.
+    pub fn default_handler<F>(mut self, handler: F) -> Self
+        where
+            F: Fn(ServiceResponse<B>) -> ServiceResponse<EitherBody<B>> + 'static,
+    {
+        self.default_handler = Some(Rc::new(handler));
+        self
+    }
+
+    /// Registers a callback invoked whenever a >299 response, or a
+    /// downstream `Err`, is observed, so callers can emit logs or metrics
+    /// without writing their own middleware layer.
+    pub fn on_error<F>(mut self, callback: F) -> Self
+        where
+            F: Fn(StatusCode, &str) + 'static,
+    {
+        self.on_error = Some(Rc::new(callback));
+        self
+    }
+
+    /// Controls whether a successful response's `Content-Type` is
+    /// overwritten with `application/json` even when one is already set.
+    ///
+    /// By default (`false`) the middleware only sets `Content-Type` when
+    /// the response doesn't already declare one, so streamed files, HTML,
+    /// or other binary payloads pass through untouched.
+    pub fn force_json_content_type(mut self, force: bool) -> Self {
+        self.force_json_content_type = force;
+        self
     }
 }
 
-impl<S, B> Transform<S, ServiceRequest> for JsonMiddleware
+impl<S, B> Transform<S, ServiceRequest> for JsonMiddleware<B>
     where
         S: Service<ServiceRequest, Response=ServiceResponse<B>, Error=Error>,
         S::Future: 'static,
-        B: 'static,
+        B: MessageBody + 'static,
 {
     type Response = ServiceResponse<EitherBody<B>>;
     type Error = Error;
-    type Transform = JsonErrorMiddlewareDefinition<S>;
+    type Transform = JsonErrorMiddlewareDefinition<S, B>;
     type InitError = ();
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(JsonErrorMiddlewareDefinition { service: service }))
+        ready(Ok(JsonErrorMiddlewareDefinition {
+            service,
+            handlers: self.handlers.clone(),
+            default_handler: self.default_handler.clone(),
+            format: self.format,
+            error_field: self.error_field.clone(),
+            message_field: self.message_field.clone(),
+            extra_fields: self.extra_fields.clone(),
+            extra_headers: self.extra_headers.clone(),
+            on_error: self.on_error.clone(),
+            force_json_content_type: self.force_json_content_type,
+        }))
     }
 }
 
@@ -95,8 +384,12 @@ impl<S, B> Transform<S, ServiceRequest> for JsonMiddleware
 mod tests {
     use std::ops::Range;
 
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
     use actix_web::{App, HttpResponse, HttpResponseBuilder, test, web};
     use actix_web::http::StatusCode;
+    use actix_web::error::ErrorInternalServerError;
     use test::TestRequest;
 
     use super::*;
@@ -137,7 +430,7 @@ mod tests {
     async fn test_get_404_json_content_type() {
         let app = test::init_service(
             App::new()
-                .wrap(JsonMiddleware)
+                .wrap(JsonMiddleware::new())
                 .route("/status/{code}", web::route().to(status_handler))
         ).await;
 
@@ -162,7 +455,7 @@ mod tests {
         let test_uri = "/foo";
         let app = test::init_service(
             App::new()
-                .wrap(JsonMiddleware)
+                .wrap(JsonMiddleware::new())
         ).await;
 
         let req = TestRequest::get().uri(test_uri).to_request();
@@ -177,6 +470,273 @@ mod tests {
         assert_eq!(resp_json.error, 404)
     }
 
+    /// A handler returning `Err` should end up as a JSON error body using
+    /// the error's own status code. actix-web renders the handler's `Err`
+    /// as a response before the middleware sees it, so this goes through
+    /// the middleware's `Ok` branch.
+    #[actix_web::test]
+    async fn test_downstream_error_is_not_panic() {
+        async fn failing_handler() -> Result<HttpResponse, Error> {
+            Err(ErrorInternalServerError("boom"))
+        }
+
+        let app = test::init_service(
+            App::new()
+                .wrap(JsonMiddleware::new())
+                .route("/boom", web::route().to(failing_handler))
+        ).await;
+
+        let req = TestRequest::get().uri("/boom").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 500);
+
+        let req_json = TestRequest::get().uri("/boom").to_request();
+        let resp_json: JsonErrorMessage = test::call_and_read_body_json(&app, req_json).await;
+        assert_eq!(resp_json.error, 500);
+    }
+
+    /// `on_error` should fire both for a plain >299 response and for a
+    /// handler returning `Err` (rendered by actix-web as a 500 response),
+    /// with the observed status code.
+    #[actix_web::test]
+    async fn test_on_error_hook_observes_both_paths() {
+        async fn failing_handler() -> Result<HttpResponse, Error> {
+            Err(ErrorInternalServerError("boom"))
+        }
+
+        let observed = Rc::new(RefCell::new(Vec::new()));
+        let observed_in_hook = observed.clone();
+
+        let app = test::init_service(
+            App::new()
+                .wrap(
+                    JsonMiddleware::new()
+                        .on_error(move |status, _req| observed_in_hook.borrow_mut().push(status.as_u16()))
+                )
+                .route("/status/{code}", web::route().to(status_handler))
+                .route("/boom", web::route().to(failing_handler))
+        ).await;
+
+        let req = TestRequest::get().uri("/status/404").to_request();
+        test::call_service(&app, req).await;
+
+        let req = TestRequest::get().uri("/boom").to_request();
+        test::call_service(&app, req).await;
+
+        assert_eq!(*observed.borrow(), vec![404, 500]);
+    }
+
+    /// Renamed fields, extra static fields, and extra headers should all be
+    /// applied to the generic `ErrorFormat::Simple` body.
+    #[actix_web::test]
+    async fn test_custom_schema_and_headers() {
+        let app = test::init_service(
+            App::new()
+                .wrap(
+                    JsonMiddleware::new()
+                        .error_field("code")
+                        .message_field("reason")
+                        .extra_field("service", "billing")
+                        .header(
+                            header::HeaderName::from_static("x-correlation-source"),
+                            header::HeaderValue::from_static("json-error-middleware"),
+                        )
+                )
+                .route("/status/{code}", web::route().to(status_handler))
+        ).await;
+
+        let req = TestRequest::get().uri("/status/404").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.headers().get("x-correlation-source").unwrap(), "json-error-middleware");
+
+        let req_json = TestRequest::get().uri("/status/404").to_request();
+        let resp_json: serde_json::Value = test::call_and_read_body_json(&app, req_json).await;
+        assert_eq!(resp_json["code"], 404);
+        assert_eq!(resp_json["reason"], "404 Not Found");
+        assert_eq!(resp_json["service"], "billing");
+    }
+
+    /// Selecting `ErrorFormat::ProblemJson` should produce an RFC 7807 body
+    /// with the `application/problem+json` content type.
+    #[actix_web::test]
+    async fn test_problem_json_format() {
+        let app = test::init_service(
+            App::new()
+                .wrap(JsonMiddleware::new().format(ErrorFormat::ProblemJson))
+                .route("/status/{code}", web::route().to(status_handler))
+        ).await;
+
+        let req = TestRequest::get().uri("/status/404").to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.headers().get("content-type").unwrap(), "application/problem+json");
+
+        let req_body = TestRequest::get().uri("/status/404").to_request();
+        let problem: ProblemDetails = test::call_and_read_body_json(&app, req_body).await;
+        assert_eq!(problem.type_uri, "about:blank");
+        assert_eq!(problem.title, "Not Found");
+        assert_eq!(problem.status, 404);
+        assert_eq!(problem.instance, "/status/404");
+    }
+
+    /// A handler that already returns JSON should have its body passed
+    /// through untouched rather than replaced with the generic message.
+    #[actix_web::test]
+    async fn test_existing_json_body_passes_through() {
+        async fn json_error_handler() -> HttpResponse {
+            HttpResponseBuilder::new(StatusCode::BAD_REQUEST).json(serde_json::json!({ "reason": "nope" }))
+        }
+
+        let app = test::init_service(
+            App::new()
+                .wrap(JsonMiddleware::new())
+                .route("/json-error", web::route().to(json_error_handler))
+        ).await;
+
+        let req = TestRequest::get().uri("/json-error").to_request();
+        let resp: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(resp["reason"], "nope");
+    }
+
+    /// A body with an uppercase `Content-Type` should still be recognized as
+    /// JSON and passed through, while a body with a merely JSON-*prefixed*
+    /// media type like `application/jsonp` should not be mistaken for JSON
+    /// and should instead be merged into `detail`.
+    #[actix_web::test]
+    async fn test_content_type_match_is_case_insensitive_and_not_a_prefix_match() {
+        async fn uppercase_json_handler() -> HttpResponse {
+            HttpResponseBuilder::new(StatusCode::BAD_REQUEST)
+                .content_type("APPLICATION/JSON")
+                .body(r#"{"reason":"nope"}"#)
+        }
+
+        async fn jsonp_handler() -> HttpResponse {
+            HttpResponseBuilder::new(StatusCode::BAD_REQUEST)
+                .content_type("application/jsonp")
+                .body("callback({})")
+        }
+
+        let app = test::init_service(
+            App::new()
+                .wrap(JsonMiddleware::new())
+                .route("/upper-json-error", web::route().to(uppercase_json_handler))
+                .route("/jsonp-error", web::route().to(jsonp_handler))
+        ).await;
+
+        let req = TestRequest::get().uri("/upper-json-error").to_request();
+        let resp: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(resp["reason"], "nope");
+
+        let req = TestRequest::get().uri("/jsonp-error").to_request();
+        let resp: JsonErrorMessage = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(resp.error, 400);
+        assert_eq!(resp.detail.as_deref(), Some("callback({})"));
+    }
+
+    /// A handler that returns a non-JSON body should have that body merged
+    /// into the generic message as a `detail` field instead of discarded.
+    #[actix_web::test]
+    async fn test_non_json_body_is_merged_as_detail() {
+        async fn text_error_handler() -> HttpResponse {
+            HttpResponseBuilder::new(StatusCode::BAD_REQUEST).body("bad request body")
+        }
+
+        let app = test::init_service(
+            App::new()
+                .wrap(JsonMiddleware::new())
+                .route("/text-error", web::route().to(text_error_handler))
+        ).await;
+
+        let req = TestRequest::get().uri("/text-error").to_request();
+        let resp: JsonErrorMessage = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(resp.error, 400);
+        assert_eq!(resp.detail.as_deref(), Some("bad request body"));
+    }
+
+    /// Registering a handler for a specific status code should be used in
+    /// place of the generic `{error, message}` body for that code only.
+    #[actix_web::test]
+    async fn test_per_status_handler_overrides_default() {
+        let app = test::init_service(
+            App::new()
+                .wrap(
+                    JsonMiddleware::new()
+                        .handler(StatusCode::NOT_FOUND, |res| {
+                            let response = HttpResponseBuilder::new(StatusCode::NOT_FOUND)
+                                .json(serde_json::json!({ "custom": true }))
+                                .map_into_right_body();
+                            ServiceResponse::into_response(res, response)
+                        })
+                )
+                .route("/status/{code}", web::route().to(status_handler))
+        ).await;
+
+        let req = TestRequest::get().uri("/status/404").to_request();
+        let resp: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(resp["custom"], true);
+    }
+
+    /// A `default_handler` should apply to any status code > 299 that has no
+    /// specific handler registered.
+    #[actix_web::test]
+    async fn test_default_handler_applies_to_unregistered_codes() {
+        let app = test::init_service(
+            App::new()
+                .wrap(
+                    JsonMiddleware::new()
+                        .default_handler(|res| {
+                            let response = HttpResponseBuilder::new(res.status())
+                                .json(serde_json::json!({ "fallback": true }))
+                                .map_into_right_body();
+                            ServiceResponse::into_response(res, response)
+                        })
+                )
+                .route("/status/{code}", web::route().to(status_handler))
+        ).await;
+
+        let req = TestRequest::get().uri("/status/500").to_request();
+        let resp: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(resp["fallback"], true);
+    }
+
+    /// A successful response that already declares a `Content-Type` should
+    /// be left untouched by default rather than overwritten.
+    #[actix_web::test]
+    async fn test_success_content_type_preserved_by_default() {
+        async fn html_handler() -> HttpResponse {
+            HttpResponseBuilder::new(StatusCode::OK).content_type("text/html").body("<p>hi</p>")
+        }
+
+        let app = test::init_service(
+            App::new()
+                .wrap(JsonMiddleware::new())
+                .route("/page", web::route().to(html_handler))
+        ).await;
+
+        let req = TestRequest::get().uri("/page").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.headers().get("content-type").unwrap(), "text/html");
+    }
+
+    /// `force_json_content_type(true)` should restore the old behavior of
+    /// overwriting an existing `Content-Type` on success responses.
+    #[actix_web::test]
+    async fn test_force_json_content_type_overwrites() {
+        async fn html_handler() -> HttpResponse {
+            HttpResponseBuilder::new(StatusCode::OK).content_type("text/html").body("<p>hi</p>")
+        }
+
+        let app = test::init_service(
+            App::new()
+                .wrap(JsonMiddleware::new().force_json_content_type(true))
+                .route("/page", web::route().to(html_handler))
+        ).await;
+
+        let req = TestRequest::get().uri("/page").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.headers().get("content-type").unwrap(), "application/json");
+    }
+
     // Status Code Range Tests
 
     /// Tests Iteratively GET Requests For HTTP Status Codes 200-299
@@ -249,7 +809,7 @@ mod tests {
     /// Tests Iteratively PUT Requests For HTTP Status Codes 500-512
     #[actix_web::test]
     async fn test_500s_put_errors() {
-        help_test_put_by_range(500u16..412u16).await
+        help_test_put_by_range(500u16..512u16).await
     }
 
 
@@ -259,7 +819,7 @@ mod tests {
     async fn help_test_post_by_range(status_range: Range<u16>) {
         let app = test::init_service(
             App::new()
-                .wrap(JsonMiddleware)
+                .wrap(JsonMiddleware::new())
                 .route("/status/{code}", web::route().to(status_handler))
         ).await;
 
@@ -284,7 +844,7 @@ mod tests {
     async fn help_test_get_by_range(status_range: Range<u16>) {
         let app = test::init_service(
             App::new()
-                .wrap(JsonMiddleware)
+                .wrap(JsonMiddleware::new())
                 .route("/status/{code}", web::route().to(status_handler))
         ).await;
 
@@ -308,7 +868,7 @@ mod tests {
     async fn help_test_put_by_range(status_range: Range<u16>) {
         let app = test::init_service(
             App::new()
-                .wrap(JsonMiddleware)
+                .wrap(JsonMiddleware::new())
                 .route("/status/{code}", web::route().to(status_handler))
         ).await;
 
@@ -327,4 +887,4 @@ mod tests {
             }
         }
     }
-}
\ No newline at end of file
+}
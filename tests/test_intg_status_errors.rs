@@ -27,7 +27,7 @@ async fn status_handler(path: web::Path<(u16, )>) -> HttpResponse {
 async fn test_intg_middleware_test_handler() {
     let app = test::init_service(
         App::new()
-            .wrap(JsonMiddleware)
+            .wrap(JsonMiddleware::new())
             .route("/status/{code}", web::route().to(status_handler))
     ).await;
 
@@ -111,7 +111,7 @@ async fn test_intg_400s_put_errors() {
 /// Tests Iteratively PUT Requests For HTTP Status Codes 500-512
 #[actix_web::test]
 async fn test_intg_500s_put_errors() {
-    help_request_by_range(500u16..412u16, &Method::PUT).await
+    help_request_by_range(500u16..512u16, &Method::PUT).await
 }
 
 // Test Helpers
@@ -135,7 +135,7 @@ async fn help_test_put_by_range(status_range: Range<u16>) {
 async fn help_request_by_range(status_range: Range<u16>, method_type: &Method) {
     let app = test::init_service(
         App::new()
-            .wrap(JsonMiddleware)
+            .wrap(JsonMiddleware::new())
             .route("/status/{code}", web::route().to(status_handler))
     ).await;
 